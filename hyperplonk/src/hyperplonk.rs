@@ -1,5 +1,7 @@
 use ark_ec::pairing::Pairing;
-use ark_ff::fields::Field;
+use ark_ff::fields::{Field, PrimeField};
+use ark_ff::Zero;
+use ark_serialize::CanonicalSerialize;
 use ark_std::UniformRand;
 use dist_primitive::{
     dacc_product::acc_product,
@@ -7,15 +9,96 @@ use dist_primitive::{
     dsumcheck::sumcheck_product,
     mle::fix_variable,
 };
+use rand::{rngs::StdRng, SeedableRng};
+use sha2::{Digest, Sha256};
 
 use dist_primitive::random_evaluations;
 use mpc_net::{end_timer, start_timer};
 
+/// Batches polynomials opened at the same point into one opening (halo2 multiopen scheme).
+pub trait BatchedPolynomialCommitment<E: Pairing> {
+    /// Combines `polys` (evaluated at `point`, committed to as `coms` in the same order) and opens once.
+    fn batch_open(
+        &self,
+        polys: &[&Vec<E::ScalarField>],
+        coms: &[E::G1],
+        point: &[E::ScalarField],
+    ) -> (E::ScalarField, Vec<E::G1>);
+
+    /// Recombines `coms` and checks the opening produced by `batch_open`.
+    fn batch_verify(
+        &self,
+        coms: &[E::G1],
+        point: &[E::ScalarField],
+        opening: &(E::ScalarField, Vec<E::G1>),
+    ) -> bool;
+}
+
+impl<E: Pairing> BatchedPolynomialCommitment<E> for PolynomialCommitment<E> {
+    fn batch_open(
+        &self,
+        polys: &[&Vec<E::ScalarField>],
+        coms: &[E::G1],
+        point: &[E::ScalarField],
+    ) -> (E::ScalarField, Vec<E::G1>) {
+        let rho = fiat_shamir_rho::<E>(coms, point);
+        let combined = combine_evaluations(polys, rho);
+        self.open(&combined, point)
+    }
+
+    fn batch_verify(
+        &self,
+        coms: &[E::G1],
+        point: &[E::ScalarField],
+        opening: &(E::ScalarField, Vec<E::G1>),
+    ) -> bool {
+        let rho = fiat_shamir_rho::<E>(coms, point);
+        let mut rho_i = E::ScalarField::ONE;
+        let mut combined_com = E::G1::zero();
+        for com in coms {
+            combined_com += *com * rho_i;
+            rho_i *= rho;
+        }
+        self.verify(&combined_com, point, opening)
+    }
+}
+
+// g = sum rho^i * f_i over the shared evaluation point.
+fn combine_evaluations<F: Field>(polys: &[&Vec<F>], rho: F) -> Vec<F> {
+    let len = polys[0].len();
+    let mut combined = vec![F::ZERO; len];
+    let mut rho_i = F::ONE;
+    for poly in polys {
+        for (c, p) in combined.iter_mut().zip(poly.iter()) {
+            *c += rho_i * *p;
+        }
+        rho_i *= rho;
+    }
+    combined
+}
+
+// Derives rho by hashing the commitments and point (Fiat-Shamir, not sampled).
+fn fiat_shamir_rho<E: Pairing>(coms: &[E::G1], point: &[E::ScalarField]) -> E::ScalarField {
+    let mut hasher = Sha256::new();
+    for com in coms {
+        let mut bytes = Vec::new();
+        com.serialize_compressed(&mut bytes)
+            .expect("canonical serialization of a commitment should not fail");
+        hasher.update(&bytes);
+    }
+    for p in point {
+        let mut bytes = Vec::new();
+        p.serialize_compressed(&mut bytes)
+            .expect("canonical serialization of a field element should not fail");
+        hasher.update(&bytes);
+    }
+    E::ScalarField::from_le_bytes_mod_order(&hasher.finalize())
+}
+
 /// This is a simplified version without any optimization to simulate the complexity.
 pub fn local_hyperplonk<E: Pairing>(
     n: usize, // n is the log2 of the circuit size
-) -> ((Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<(<E as Pairing>::G1, (<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>))>), (Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<<E as Pairing>::G1>, Vec<(<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>)>)) {
-    use rand::{rngs::StdRng, SeedableRng};
+) -> ((Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<<E as Pairing>::G1>), (Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<<E as Pairing>::G1>), (<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>)) {
     let rng = &mut StdRng::from_entropy();
     let gate_count = 1 << n;
     // Witness polynomial M (with n+2 variables)
@@ -71,7 +154,9 @@ pub fn local_hyperplonk<E: Pairing>(
     // Gate identity
     let gate_timer = start_timer!("Gate identity");
     let mut gate_identity_proofs = Vec::new();
-    let mut gate_identity_commitments = Vec::new();
+    let gate_identity_commitments = vec![
+        com_a, com_b, com_c, com_in, com_q1, com_q2, com_ssigma_a, com_ssigma_b, com_ssigma_c,
+    ];
     // Sumcheck F(x)=eq(x)*[q_1(x)*(a(x)+b(x))+q_2(x)*a(x)*b(x)-c(x)+I(x)]
     // In original Hyperplonk this is done with a virtual circuit.
     // We use different sumcheck product to simulate it for implementation simplicity. The computation complexity is the same.
@@ -99,7 +184,6 @@ pub fn local_hyperplonk<E: Pairing>(
     // Wire identity
     let mut wiring_proofs = Vec::new();
     let mut wiring_commits = Vec::new();
-    let mut wiring_opens = Vec::new();
     let wire_timer = start_timer!("Wire identity");
     // Compute f, g
     // f(x) = \prod (w_i(x) + \beta*sid_i(x) + \gamma)
@@ -120,20 +204,13 @@ pub fn local_hyperplonk<E: Pairing>(
     let h = num.iter().zip(den.iter()).map(|(a, b)| *a / *b).collect();
     // Compute V
     let (vx0, vx1, v1x) = acc_product(&h);
-    // Commit
-    // Open (Here we omit repeated openings on the same polynomial).
+    // Commit here; opening is deferred to the shared batched opening below.
     wiring_commits.push(commitment.commit(&h));
-    wiring_opens.push(commitment.open(&h, &challenge));
     wiring_commits.push(commitment.commit(&num));
-    wiring_opens.push(commitment.open(&num, &challenge));
     wiring_commits.push(commitment.commit(&den));
-    wiring_opens.push(commitment.open(&den, &challenge));
     wiring_commits.push(commitment.commit(&vx0));
-    wiring_opens.push(commitment.open(&vx0, &challenge));
     wiring_commits.push(commitment.commit(&vx1));
-    wiring_opens.push(commitment.open(&vx1, &challenge));
     wiring_commits.push(commitment.commit(&v1x));
-    wiring_opens.push(commitment.open(&v1x, &challenge));
     // Sumcheck for F(x)=eq(x)*(v1x-vx0*vx1).
     wiring_proofs.push(sumcheck_product(&eq, &v1x, &challenge));
     wiring_proofs.push(sumcheck_product(&eq, &vx0, &challenge));
@@ -143,17 +220,32 @@ pub fn local_hyperplonk<E: Pairing>(
     wiring_proofs.push(sumcheck_product(&h, &num, &challenge));
     end_timer!(wire_timer);
 
-    // Open
+    // All 15 polynomials above share the `challenge` point, so batch their opening.
     let open_timer = start_timer!("Open");
-    gate_identity_commitments.push((com_a, commitment.open(&a_evals, &challenge)));
-    gate_identity_commitments.push((com_b, commitment.open(&b_evals, &challenge)));
-    gate_identity_commitments.push((com_c, commitment.open(&c_evals, &challenge)));
-    gate_identity_commitments.push((com_in, commitment.open(&input, &challenge)));
-    gate_identity_commitments.push((com_q1, commitment.open(&q1, &challenge)));
-    gate_identity_commitments.push((com_q2, commitment.open(&q2, &challenge)));
-    gate_identity_commitments.push((com_ssigma_a, commitment.open(&ssigma_a_evals, &challenge)));
-    gate_identity_commitments.push((com_ssigma_b, commitment.open(&ssigma_b_evals, &challenge)));
-    gate_identity_commitments.push((com_ssigma_c, commitment.open(&ssigma_c_evals, &challenge)));
+    let shared_point_polys: Vec<&Vec<E::ScalarField>> = vec![
+        &a_evals,
+        &b_evals,
+        &c_evals,
+        &input,
+        &q1,
+        &q2,
+        &ssigma_a_evals,
+        &ssigma_b_evals,
+        &ssigma_c_evals,
+        &h,
+        &num,
+        &den,
+        &vx0,
+        &vx1,
+        &v1x,
+    ];
+    let shared_point_coms: Vec<E::G1> = gate_identity_commitments
+        .iter()
+        .chain(wiring_commits.iter())
+        .copied()
+        .collect();
+    let batched_opening = commitment.batch_open(&shared_point_polys, &shared_point_coms, &challenge);
+    debug_assert!(commitment.batch_verify(&shared_point_coms, &challenge, &batched_opening));
     end_timer!(open_timer);
 
     end_timer!(prover_timer);
@@ -161,14 +253,14 @@ pub fn local_hyperplonk<E: Pairing>(
     end_timer!(timer_all);
     (
         (gate_identity_proofs, gate_identity_commitments),
-        (wiring_proofs, wiring_commits, wiring_opens),
+        (wiring_proofs, wiring_commits),
+        batched_opening,
     )
 }
 
 pub fn local_hyperplonkpp<E: Pairing>(
     n: usize, // n is the log2 of the circuit size
-) -> ((Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<(<E as Pairing>::G1, (<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>))>), (Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<<E as Pairing>::G1>, Vec<(<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>)>)) {
-    use rand::{rngs::StdRng, SeedableRng};
+) -> ((Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<<E as Pairing>::G1>), (Vec<Vec<(<E as Pairing>::ScalarField, <E as Pairing>::ScalarField, <E as Pairing>::ScalarField)>>, Vec<<E as Pairing>::G1>), (<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>), (<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>), (<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>), (<E as Pairing>::ScalarField, Vec<<E as Pairing>::G1>)) {
     let rng = &mut StdRng::from_entropy();
     let gate_count = 1 << n;
     // Witness polynomial M (with n+2 variables)
@@ -226,7 +318,9 @@ pub fn local_hyperplonkpp<E: Pairing>(
     // Gate identity
     let gate_timer = start_timer!("Gate identity");
     let mut gate_identity_proofs = Vec::new();
-    let mut gate_identity_commitments = Vec::new();
+    let gate_identity_commitments = vec![
+        com_a, com_b, com_c, com_in, com_q1, com_q2, com_ssigma_a, com_ssigma_b, com_ssigma_c,
+    ];
     // Sumcheck F(x)=eq(x)*[q_1(x)*(a(x)+b(x))+q_2(x)*a(x)*b(x)-c(x)+I(x)]
     // In original Hyperplonk this is done with a virtual circuit.
     // We use different sumcheck product to simulate it for implementation simplicity. The computation complexity is the same.
@@ -254,14 +348,14 @@ pub fn local_hyperplonkpp<E: Pairing>(
     // Wire identity
     let mut wiring_proofs = Vec::new();
     let mut wiring_commits = Vec::new();
-    let mut wiring_opens = Vec::new();
     let wire_timer = start_timer!("Wire identity");
     let s = random_evaluations(gate_count * 4);
     wiring_commits.push(commitment.commit(&s));
     wiring_proofs.push(sumcheck_product(&m, &s, &challengep2));
-    wiring_opens.push(commitment.open(&s, &challengep2));
-    wiring_opens.push(commitment.open(&m, &challengep2));
-    wiring_opens.push(commitment.open(&m, &challengep2_2));
+    // s and m are each opened alone (m has no commitment), so batching buys nothing here.
+    let s_opening = commitment.open(&s, &challengep2);
+    let m_opening_p2 = commitment.open(&m, &challengep2);
+    let m_opening = commitment.open(&m, &challengep2_2);
     // Compute f, g
     // f(x) = \prod (w_i(x) + \beta*sid_i(x) + \gamma)
     let num: Vec<_> = (0..gate_count)
@@ -281,20 +375,13 @@ pub fn local_hyperplonkpp<E: Pairing>(
     let h = num.iter().zip(den.iter()).map(|(a, b)| *a / *b).collect();
     // Compute V
     let (vx0, vx1, v1x) = acc_product(&h);
-    // Commit
-    // Open (Here we omit repeated openings on the same polynomial).
+    // Commit here; opening is deferred to the shared batched opening below.
     wiring_commits.push(commitment.commit(&h));
-    wiring_opens.push(commitment.open(&h, &challenge));
     wiring_commits.push(commitment.commit(&num));
-    wiring_opens.push(commitment.open(&num, &challenge));
     wiring_commits.push(commitment.commit(&den));
-    wiring_opens.push(commitment.open(&den, &challenge));
     wiring_commits.push(commitment.commit(&vx0));
-    wiring_opens.push(commitment.open(&vx0, &challenge));
     wiring_commits.push(commitment.commit(&vx1));
-    wiring_opens.push(commitment.open(&vx1, &challenge));
     wiring_commits.push(commitment.commit(&v1x));
-    wiring_opens.push(commitment.open(&v1x, &challenge));
     // Sumcheck for F(x)=eq(x)*(v1x-vx0*vx1).
     wiring_proofs.push(sumcheck_product(&eq, &v1x, &challenge));
     wiring_proofs.push(sumcheck_product(&eq, &vx0, &challenge));
@@ -304,17 +391,33 @@ pub fn local_hyperplonkpp<E: Pairing>(
     wiring_proofs.push(sumcheck_product(&h, &num, &challenge));
     end_timer!(wire_timer);
 
-    // Open
+    // All 15 polynomials above share the `challenge` point, so batch their opening.
     let open_timer = start_timer!("Open");
-    gate_identity_commitments.push((com_a, commitment.open(&a_evals, &challenge)));
-    gate_identity_commitments.push((com_b, commitment.open(&b_evals, &challenge)));
-    gate_identity_commitments.push((com_c, commitment.open(&c_evals, &challenge)));
-    gate_identity_commitments.push((com_in, commitment.open(&input, &challenge)));
-    gate_identity_commitments.push((com_q1, commitment.open(&q1, &challenge)));
-    gate_identity_commitments.push((com_q2, commitment.open(&q2, &challenge)));
-    gate_identity_commitments.push((com_ssigma_a, commitment.open(&ssigma_a_evals, &challenge)));
-    gate_identity_commitments.push((com_ssigma_b, commitment.open(&ssigma_b_evals, &challenge)));
-    gate_identity_commitments.push((com_ssigma_c, commitment.open(&ssigma_c_evals, &challenge)));
+    let shared_point_polys: Vec<&Vec<E::ScalarField>> = vec![
+        &a_evals,
+        &b_evals,
+        &c_evals,
+        &input,
+        &q1,
+        &q2,
+        &ssigma_a_evals,
+        &ssigma_b_evals,
+        &ssigma_c_evals,
+        &h,
+        &num,
+        &den,
+        &vx0,
+        &vx1,
+        &v1x,
+    ];
+    // wiring_commits[0] is com_s, opened separately above; skip it here.
+    let shared_point_coms: Vec<E::G1> = gate_identity_commitments
+        .iter()
+        .chain(wiring_commits[1..].iter())
+        .copied()
+        .collect();
+    let batched_opening = commitment.batch_open(&shared_point_polys, &shared_point_coms, &challenge);
+    debug_assert!(commitment.batch_verify(&shared_point_coms, &challenge, &batched_opening));
     end_timer!(open_timer);
 
     end_timer!(prover_timer);
@@ -322,6 +425,10 @@ pub fn local_hyperplonkpp<E: Pairing>(
     end_timer!(timer_all);
     (
         (gate_identity_proofs, gate_identity_commitments),
-        (wiring_proofs, wiring_commits, wiring_opens),
+        (wiring_proofs, wiring_commits),
+        batched_opening,
+        s_opening,
+        m_opening_p2,
+        m_opening,
     )
 }